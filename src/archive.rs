@@ -0,0 +1,115 @@
+//! Zero-copy, read-only loading of large dumps via [rkyv], for read-heavy
+//! workloads where deserializing a whole [Database](crate::Database) with
+//! [Database::load_db](crate::Database::load_db) would be wasteful.
+//!
+//! Unlike the rest of tinydb, an [ArchivedDatabase] never materializes a
+//! fresh [std::collections::HashSet]: the dump is memory-mapped and queries
+//! are served directly against the archived bytes, so the handle is
+//! read-only for as long as it's open.
+
+use crate::{hash, io_to_dberror, path_to_dberror, write_dump_atomically, Database, DatabaseError};
+use memmap2::Mmap;
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::ser::Serializer as RkyvSerializer;
+use rkyv::{Archive, Archived};
+use std::collections::HashSet;
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::Path;
+
+impl<T, S> Database<T, S>
+where
+    T: hash::Hash + Eq + Archive + rkyv::Serialize<AllocSerializer<256>>,
+    Archived<T>: hash::Hash + Eq,
+    S: crate::Serializer<T>,
+{
+    /// Serializes [Database]'s items with [rkyv] and writes them to
+    /// [Database::save_path], so they can later be read back without a full
+    /// deserialize via [ArchivedDatabase::load_archived].
+    pub fn dump_archived(&self) -> Result<(), DatabaseError> {
+        let mut serializer = AllocSerializer::<256>::default();
+        serializer
+            .serialize_value(&self.items)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+        let archived_bytes = serializer.into_serializer().into_inner();
+        let definate_path = path_to_dberror(self.save_path.as_ref())?;
+
+        write_dump_atomically(&definate_path, &archived_bytes)
+    }
+}
+
+/// A read-only, memory-mapped handle onto an [rkyv]-archived dump produced
+/// by [Database::dump_archived].
+///
+/// The backing file stays memory-mapped for the lifetime of this handle, so
+/// [ArchivedDatabase::query_item_by] serves lookups directly against the
+/// archived bytes without ever allocating a fresh [HashSet].
+pub struct ArchivedDatabase<T: Archive> {
+    mmap: Mmap,
+    _marker: PhantomData<T>,
+}
+
+impl<T: hash::Hash + Eq + Archive> ArchivedDatabase<T>
+where
+    Archived<T>: hash::Hash + Eq,
+{
+    /// Memory-maps an archived dump written by [Database::dump_archived].
+    ///
+    /// # Safety caveats
+    ///
+    /// The returned handle borrows the file's bytes directly; it is
+    /// read-only, and the file must not be modified by another process while
+    /// the handle is alive. The archived root is trusted as-is (no
+    /// [rkyv] validation pass is run), so only load files written by
+    /// [Database::dump_archived] itself.
+    pub fn load_archived(path: &Path) -> Result<Self, DatabaseError> {
+        let dump_file = io_to_dberror(File::open(path))?;
+        let mmap = io_to_dberror(unsafe { Mmap::map(&dump_file) })?;
+
+        Ok(ArchivedDatabase {
+            mmap,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The archived root of the mapped [HashSet], borrowed for as long as
+    /// this handle is alive.
+    pub fn archived_items(&self) -> &Archived<HashSet<T>> {
+        unsafe { rkyv::archived_root::<HashSet<T>>(&self.mmap) }
+    }
+
+    /// Queries the archived items for every entry matching a predicate,
+    /// mirroring [Database::query_item_by](crate::Database::query_item_by)
+    /// but operating on [Archived] items rather than deserializing them.
+    ///
+    /// This is a linear scan; prefer [ArchivedDatabase::contains]/
+    /// [ArchivedDatabase::get] for an exact-item lookup, which hashes
+    /// straight into the archived set instead.
+    pub fn query_item_by<F: Fn(&Archived<T>) -> bool>(&self, predicate: F) -> Vec<&Archived<T>> {
+        self.archived_items()
+            .iter()
+            .filter(|item| predicate(item))
+            .collect()
+    }
+
+    /// Returns whether `key` is present in the archived set, hashing
+    /// directly into [ArchivedDatabase::archived_items] rather than
+    /// scanning every entry.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + hash::Hash + Eq,
+        Archived<T>: std::borrow::Borrow<Q>,
+    {
+        self.archived_items().contains(key)
+    }
+
+    /// Looks up the archived item equal to `key`, hashing directly into
+    /// [ArchivedDatabase::archived_items] rather than scanning every entry.
+    pub fn get<Q>(&self, key: &Q) -> Option<&Archived<T>>
+    where
+        Q: ?Sized + hash::Hash + Eq,
+        Archived<T>: std::borrow::Borrow<Q>,
+    {
+        self.archived_items().get(key)
+    }
+}
@@ -0,0 +1,167 @@
+//! Multiple named tables kept under a single save file, for when one
+//! [Database](crate::Database) per logical table is overkill and a single
+//! environment holding several collections is a better fit.
+
+use crate::{hash, io_to_dberror, path_to_dberror, write_dump_atomically, DatabaseError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+/// The on-disk representation of a [Collections] environment, holding every
+/// named table's items in one file.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned + hash::Hash + Eq")]
+struct CollectionsDump<T: hash::Hash + Eq> {
+    label: String,
+    strict_dupes: bool,
+    tables: HashMap<String, HashSet<T>>,
+}
+
+/// A save-file environment holding several named, independent item tables.
+///
+/// Unlike [Database](crate::Database), which holds a single table of items,
+/// `Collections` lets you keep multiple logical tables (e.g. "users" and
+/// "sessions") under one [Collections::label]/[Collections::save_path],
+/// dumped/loaded together in a single call.
+pub struct Collections<T: hash::Hash + Eq> {
+    pub label: String,
+    pub save_path: Option<PathBuf>,
+    pub strict_dupes: bool,
+    tables: HashMap<String, HashSet<T>>,
+}
+
+/// A handle to a single table inside a [Collections] environment, supporting
+/// the same essential operations as [Database](crate::Database) does for its
+/// one table.
+pub struct CollectionHandle<'a, T: hash::Hash + Eq> {
+    strict_dupes: bool,
+    items: &'a mut HashSet<T>,
+}
+
+impl<'a, T: hash::Hash + Eq> CollectionHandle<'a, T> {
+    /// Adds a new item to this collection.
+    pub fn add_item(&mut self, item: T) -> Result<(), DatabaseError> {
+        if self.strict_dupes {
+            if self.items.contains(&item) {
+                return Err(DatabaseError::DupeFound);
+            }
+        }
+
+        self.items.insert(item);
+        Ok(())
+    }
+
+    /// Removes an item from this collection.
+    ///
+    /// # Errors
+    ///
+    /// Will return [DatabaseError::ItemNotFound] if the item that is
+    /// attempting to be deleted was not found.
+    pub fn remove_item(&mut self, item: T) -> Result<(), DatabaseError> {
+        if self.items.remove(&item) {
+            Ok(())
+        } else {
+            Err(DatabaseError::ItemNotFound)
+        }
+    }
+
+    /// Query this collection for a specific item.
+    pub fn query_item(&mut self, item: T) -> Option<&T> {
+        self.items.get(&item)
+    }
+}
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned + Clone> Collections<T> {
+    /// Creates a new, empty collections environment.
+    pub fn new(label: String, save_path: Option<PathBuf>, strict_dupes: bool) -> Self {
+        Collections {
+            label,
+            save_path,
+            strict_dupes,
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Creates a new, empty collection (table) with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Will return [DatabaseError::DatabaseAlreadyExists] if a collection
+    /// with that name already exists.
+    pub fn create_collection(&mut self, name: &str) -> Result<(), DatabaseError> {
+        if self.tables.contains_key(name) {
+            return Err(DatabaseError::DatabaseAlreadyExists);
+        }
+
+        self.tables.insert(name.to_string(), HashSet::new());
+        Ok(())
+    }
+
+    /// Gets a handle to an existing collection, or [None] if no collection
+    /// with that name has been created.
+    pub fn get_collection(&mut self, name: &str) -> Option<CollectionHandle<'_, T>> {
+        let strict_dupes = self.strict_dupes;
+        self.tables
+            .get_mut(name)
+            .map(|items| CollectionHandle { strict_dupes, items })
+    }
+
+    /// Dumps/saves every collection to a single binary file.
+    ///
+    /// # Saving path methods
+    ///
+    /// The collections are saved to the [Collections::save_path] defined
+    /// when generating the environment inside of [Collections::new]; no path
+    /// is derived from [Collections::label].
+    ///
+    /// # Errors
+    ///
+    /// Will return [DatabaseError::SavePathRequired] if
+    /// [Collections::save_path] is [None].
+    pub fn dump_db(&self) -> Result<(), DatabaseError> {
+        let dump = CollectionsDump {
+            label: self.label.clone(),
+            strict_dupes: self.strict_dupes,
+            tables: self.tables.clone(),
+        };
+
+        let dump_buffer =
+            bincode::serialize(&dump).map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+        let definate_path = path_to_dberror(self.save_path.as_ref())?;
+
+        write_dump_atomically(&definate_path, &dump_buffer)
+    }
+
+    /// Loads every collection from a `.tinydb` file and **erases any current
+    /// in-memory collections**.
+    ///
+    /// # Loading path methods
+    ///
+    /// The collections are loaded from the [Collections::save_path] defined
+    /// when generating the environment inside of [Collections::new]; no path
+    /// is derived from [Collections::label].
+    ///
+    /// # Errors
+    ///
+    /// Will return [DatabaseError::SavePathRequired] if
+    /// [Collections::save_path] is [None].
+    pub fn load_db(&mut self) -> Result<(), DatabaseError> {
+        let load_path = path_to_dberror(self.save_path.as_ref())?;
+
+        let mut dump_file = io_to_dberror(File::open(&load_path))?;
+        let mut dump_buffer = Vec::new();
+        io_to_dberror(dump_file.read_to_end(&mut dump_buffer))?;
+
+        let dump: CollectionsDump<T> = bincode::deserialize(&dump_buffer)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+
+        self.label = dump.label;
+        self.strict_dupes = dump.strict_dupes;
+        self.tables = dump.tables;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Bulk-loading foreign/plain datasets into a fresh [Database], for
+//! migrating data from other tools rather than only reading native
+//! `.tinydb` dumps.
+
+use crate::{hash, io_to_dberror, Database, DatabaseError, Serializer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// A summary of an [Database::import_from_json]/[Database::import_from_csv]
+/// call, reporting how many records ended up inserted versus skipped as
+/// duplicates.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    pub skipped_duplicates: usize,
+}
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned + Clone + 'static, S: Serializer<T>>
+    Database<T, S>
+{
+    /// Imports every record from a JSON array file, inserting each through
+    /// [Database::add_item] so [Database::strict_dupes] and any indexes stay
+    /// consistent.
+    pub fn import_from_json(&mut self, path: &Path) -> Result<ImportSummary, DatabaseError> {
+        let mut import_file = io_to_dberror(File::open(path))?;
+        let mut import_buffer = Vec::new();
+        io_to_dberror(import_file.read_to_end(&mut import_buffer))?;
+
+        let records: Vec<T> = serde_json::from_slice(&import_buffer)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+
+        self.import_records(records)
+    }
+
+    /// Imports every record from a CSV file, inserting each through
+    /// [Database::add_item] so [Database::strict_dupes] and any indexes stay
+    /// consistent.
+    pub fn import_from_csv(&mut self, path: &Path) -> Result<ImportSummary, DatabaseError> {
+        let mut reader = csv::Reader::from_path(path)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+
+        let mut summary = ImportSummary::default();
+
+        for record in reader.deserialize() {
+            let item: T = record.map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+
+            match self.add_item(item) {
+                Ok(()) => summary.inserted += 1,
+                Err(DatabaseError::DupeFound) => summary.skipped_duplicates += 1,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Adds every given record through [Database::add_item], counting
+    /// insertions versus skipped duplicates. Mirrors [Database::import_from_csv]
+    /// in propagating any non-duplicate error instead of swallowing it.
+    fn import_records(&mut self, records: Vec<T>) -> Result<ImportSummary, DatabaseError> {
+        let mut summary = ImportSummary::default();
+
+        for item in records {
+            match self.add_item(item) {
+                Ok(()) => summary.inserted += 1,
+                Err(DatabaseError::DupeFound) => summary.skipped_duplicates += 1,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,90 @@
+//! Secondary indexes for [Database](crate::Database), letting items be looked
+//! up by a derived key instead of only by an exact, already-constructed item
+//! (as [Database::query_item](crate::Database::query_item) requires).
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::hash;
+
+/// A boxed index mutation, applied to the type-erased `map` on add/remove.
+type IndexMutator<T> = Box<dyn Fn(&mut Box<dyn Any>, &T)>;
+
+/// A single named index kept in sync with a [Database](crate::Database)'s
+/// items on [Database::add_item](crate::Database::add_item)/
+/// [Database::remove_item](crate::Database::remove_item).
+///
+/// The key type `K` passed to [Database::create_index](crate::Database::create_index)
+/// is erased behind `map`/`insert`/`remove` so that indexes of differing key
+/// types can live side-by-side in [Database]'s `indexes` map; the closures
+/// downcast back to the concrete `HashMap<K, Vec<T>>` built at creation time.
+pub(crate) struct Index<T: hash::Hash + Eq> {
+    map: Box<dyn Any>,
+    insert: IndexMutator<T>,
+    remove: IndexMutator<T>,
+}
+
+impl<T: hash::Hash + Eq + Clone + 'static> Index<T> {
+    /// Builds an index over the given items, keyed by `key_fn`. The index
+    /// keeps its own clones of items in its buckets, both at construction and
+    /// on every [Index::on_add]; the `T: Clone` bound is captured here, so
+    /// calling [Index::on_add]/[Index::on_remove] does not require `T: Clone`
+    /// at the call site.
+    pub(crate) fn new<K, F>(items: &HashSet<T>, key_fn: F) -> Self
+    where
+        K: hash::Hash + Eq + 'static,
+        F: Fn(&T) -> K + 'static,
+    {
+        let mut map: HashMap<K, Vec<T>> = HashMap::new();
+        for item in items {
+            map.entry(key_fn(item)).or_default().push(item.clone());
+        }
+
+        let key_fn = std::rc::Rc::new(key_fn);
+        let insert_key_fn = key_fn.clone();
+        let remove_key_fn = key_fn;
+
+        let insert: IndexMutator<T> = Box::new(move |map_any, item| {
+            let map = map_any
+                .downcast_mut::<HashMap<K, Vec<T>>>()
+                .expect("index key type mismatch");
+            map.entry(insert_key_fn(item)).or_default().push(item.clone());
+        });
+
+        let remove: IndexMutator<T> = Box::new(move |map_any, item| {
+            let map = map_any
+                .downcast_mut::<HashMap<K, Vec<T>>>()
+                .expect("index key type mismatch");
+            if let Some(bucket) = map.get_mut(&remove_key_fn(item)) {
+                bucket.retain(|existing| existing != item);
+            }
+        });
+
+        Index {
+            map: Box::new(map),
+            insert,
+            remove,
+        }
+    }
+}
+
+impl<T: hash::Hash + Eq> Index<T> {
+    /// Records a newly-added item in the index.
+    pub(crate) fn on_add(&mut self, item: &T) {
+        (self.insert)(&mut self.map, item);
+    }
+
+    /// Removes a deleted item from the index.
+    pub(crate) fn on_remove(&mut self, item: &T) {
+        (self.remove)(&mut self.map, item);
+    }
+}
+
+impl<T: hash::Hash + Eq + 'static> Index<T> {
+    /// Looks up all items filed under `key`, or `None` if `K` does not match
+    /// the key type this index was created with.
+    pub(crate) fn get<K: hash::Hash + Eq + 'static>(&self, key: &K) -> Option<Vec<&T>> {
+        self.map
+            .downcast_ref::<HashMap<K, Vec<T>>>()
+            .map(|map| map.get(key).map(|items| items.iter().collect()).unwrap_or_default())
+    }
+}
@@ -14,7 +14,21 @@
 //! in-memory only database, it should preform at a reasonable speed (as it uses
 //! [HashSet] underneith).
 
-use std::collections::HashSet;
+mod archive;
+mod collections;
+mod import;
+mod index;
+mod serializer;
+
+pub use archive::ArchivedDatabase;
+pub use collections::{CollectionHandle, Collections};
+pub use import::ImportSummary;
+pub use serializer::{Bincode, Json, Serializer, Yaml};
+
+use index::Index;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::hash;
 use std::io::prelude::*;
@@ -36,60 +50,107 @@ pub enum DatabaseError {
 
     /// Misc [std::io::Error] that could not be properly handled.
     IOError(std::io::Error),
+
+    /// A dump could not be serialized, or a loaded dump could not be
+    /// deserialized, by the database's [Serializer].
+    SerializationError(String),
+
+    /// Attempted to create a collection, in a [crate::Collections]
+    /// environment, under a name that already exists.
+    DatabaseAlreadyExists,
 }
 
 /// The primary database structure, allowing storage of a given generic.
 ///
 /// The generic type used should primarily be structures as they resemble a
-/// conventional database model and should implament [hash::Hash] and [Eq].
+/// conventional database model and should implament [hash::Hash] and [Eq], as
+/// well as [Serialize]/[DeserializeOwned] so it can be written to/read from a
+/// [Database::dump_db]/[Database::load_db] call.
+///
+/// The `S` generic picks the on-disk encoding used by [Database::dump_db]/
+/// [Database::load_db] and defaults to the compact [Bincode] format; use
+/// [Json] or [Yaml] if you need human-readable, diffable dumps instead.
 ///
 /// # Essential operations
 ///
-/// - Create: [Database::new]   
-/// - Query: [Database::query_item]
+/// - Create: [Database::new]
+/// - Query: [Database::query_item], [Database::query_item_by], [Database::query_by_index]
 /// - Update: [Database::update_item]
 /// - Delete: [Database::remove_item]
 /// - Read all: [Database::read_db]
 /// - Dump: [Database::dump_db]
 /// - Load: [Database::load_db]
-pub struct Database<T: hash::Hash + Eq> {
+pub struct Database<T: hash::Hash + Eq, S: Serializer<T> = Bincode> {
     pub label: String,
     pub save_path: Option<PathBuf>,
     pub strict_dupes: bool,
+    pub serializer: S,
+    items: HashSet<T>,
+    indexes: HashMap<String, Index<T>>,
+}
+
+/// The on-disk representation of a [Database], used by [Database::load_db]
+/// so that [Database::label] and [Database::strict_dupes] survive a
+/// round-trip alongside the stored items.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned + hash::Hash + Eq")]
+pub struct DatabaseDump<T: hash::Hash + Eq> {
+    label: String,
+    strict_dupes: bool,
     items: HashSet<T>,
 }
 
-impl<T: hash::Hash + Eq> Database<T> {
-    /// Creates a new database instance.
+/// A borrowing view of [Database] with the same shape as [DatabaseDump],
+/// used by [Database::dump_db] so serializing a dump doesn't require
+/// cloning the whole item table just to hand it to a [Serializer].
+#[derive(Serialize)]
+#[serde(bound = "T: Serialize + hash::Hash + Eq")]
+pub struct DatabaseDumpRef<'a, T: hash::Hash + Eq> {
+    label: &'a str,
+    strict_dupes: bool,
+    items: &'a HashSet<T>,
+}
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned> Database<T, Bincode> {
+    /// Creates a new database instance, using the compact [Bincode] format for
+    /// [Database::dump_db]/[Database::load_db]. Use [Database::new_with_serializer]
+    /// to pick a different format.
     pub fn new(label: String, save_path: Option<PathBuf>, strict_dupes: bool) -> Self {
+        Database::new_with_serializer(label, save_path, strict_dupes, Bincode)
+    }
+}
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned, S: Serializer<T>> Database<T, S> {
+    /// Creates a new database instance, dumping/loading with the given
+    /// [Serializer] rather than the [Bincode] default.
+    pub fn new_with_serializer(
+        label: String,
+        save_path: Option<PathBuf>,
+        strict_dupes: bool,
+        serializer: S,
+    ) -> Self {
         Database {
             label: label,
             save_path: save_path,
             strict_dupes: strict_dupes,
+            serializer: serializer,
             items: HashSet::new(),
+            indexes: HashMap::new(),
         }
     }
 
-    /// Adds a new item to the in-memory database.
-    pub fn add_item(&mut self, item: T) -> Result<(), DatabaseError> {
-        if self.strict_dupes {
-            if self.items.contains(&item) {
-                return Err(DatabaseError::DupeFound);
-            }
-        }
-
-        self.items.insert(item);
-        return Ok(());
-    }
-
     /// Removes an item from the database.
-    /// 
+    ///
     /// # Errors
-    /// 
+    ///
     /// Will return [DatabaseError::ItemNotFound] if the item that is attempting
     /// to be deleted was not found.
     pub fn remove_item(&mut self, item: T) -> Result<(), DatabaseError> {
         if self.items.remove(&item) {
+            for index in self.indexes.values_mut() {
+                index.on_remove(&item);
+            }
+
             Ok(())
         } else {
             Err(DatabaseError::ItemNotFound)
@@ -101,25 +162,49 @@ impl<T: hash::Hash + Eq> Database<T> {
         self.items.get(&item)
     }
 
+    /// Query the database for every item matching a predicate, for when you
+    /// don't already hold a full item to hand to [Database::query_item].
+    pub fn query_item_by<F: Fn(&T) -> bool>(&self, predicate: F) -> Vec<&T> {
+        self.items.iter().filter(|item| predicate(item)).collect()
+    }
+
     /// Loads all into database from a `.tinydb` file and **erases any current
     /// in-memory data**.
     ///
+    /// Any indexes created with [Database::create_index] before this call are
+    /// dropped along with the old items rather than left pointing at a stale
+    /// snapshot; call [Database::create_index] again afterwards for any index
+    /// you still need.
+    ///
     /// # Loading path methods
-    /// 
+    ///
     /// The database will usually try to load `\[label\].tinydb` where `\[label\]`
     /// is the defined [Database::label] (path is reletive to where tinydb was
     /// executed).
     ///
     /// You can also overwrite this behaviour by defining a [Database::save_path]
     /// when generating the database inside of [Database::new].
-    pub fn load_db(&self) {
-        unimplemented!();
+    pub fn load_db(&mut self) -> Result<(), DatabaseError> {
+        let load_path = path_to_dberror(self.save_path.as_ref())?;
+
+        let mut dump_file = io_to_dberror(File::open(&load_path))?;
+        let mut dump_buffer = Vec::new();
+        io_to_dberror(dump_file.read_to_end(&mut dump_buffer))?;
+
+        let dump = self.serializer.deserialize(&dump_buffer)?;
+
+        self.label = dump.label;
+        self.strict_dupes = dump.strict_dupes;
+        self.items = dump.items;
+        self.indexes.clear();
+
+        Ok(())
     }
 
-    /// Dumps/saves database to a binary file.
-    /// 
+    /// Dumps/saves database to a file, encoded with [Database::serializer].
+    ///
     /// # Saving path methods
-    /// 
+    ///
     /// The database will usually save as `\[label\].tinydb` where `\[label\]`
     /// is the defined [Database::label] (path is reletive to where tinydb was
     /// executed).
@@ -127,27 +212,111 @@ impl<T: hash::Hash + Eq> Database<T> {
     /// You can also overwrite this behaviour by defining a [Database::save_path]
     /// when generating the database inside of [Database::new].
     pub fn dump_db(&self) -> Result<(), DatabaseError> {
-        let u8_dump: &[u8] = unsafe { any_as_u8_slice(self) };
-
-        let mut dump_file = self.open_db_path()?;
+        let dump = DatabaseDumpRef {
+            label: &self.label,
+            strict_dupes: self.strict_dupes,
+            items: &self.items,
+        };
 
-        io_to_dberror(dump_file.write_all(u8_dump))?;
+        let dump_buffer = self.serializer.serialize(&dump)?;
+        let definate_path = path_to_dberror(self.save_path.as_ref())?;
 
-        Ok(())
+        write_dump_atomically(&definate_path, &dump_buffer)
     }
+}
 
-    /// Opens the path given in [Database::save_path] or returns a [DatabaseError].
-    fn open_db_path(&self) -> Result<File, DatabaseError> {
-        let definate_path = path_to_dberror(self.save_path.as_ref())?;
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned + Clone, S: Serializer<T>> Database<T, S> {
+    /// Adds a new item to the in-memory database.
+    pub fn add_item(&mut self, item: T) -> Result<(), DatabaseError> {
+        if self.strict_dupes {
+            if self.items.contains(&item) {
+                return Err(DatabaseError::DupeFound);
+            }
+        }
+
+        // Skip the clone entirely when there are no indexes to keep in sync,
+        // which is the common case and otherwise regresses a plain `insert`
+        // into an unconditional copy of every inserted item.
+        if self.indexes.is_empty() {
+            self.items.insert(item);
+            return Ok(());
+        }
 
-        if definate_path.exists() {
-            io_to_dberror(std::fs::remove_file(&definate_path))?;
+        // `items.insert` is a no-op for an already-present item when
+        // `strict_dupes` is false, so only the indexes are updated when the
+        // item is actually new; otherwise they'd drift from `items` with a
+        // phantom entry.
+        if self.items.insert(item.clone()) {
+            for index in self.indexes.values_mut() {
+                index.on_add(&item);
+            }
         }
 
-        io_to_dberror(File::create(&definate_path))
+        Ok(())
+    }
+}
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned + Clone + 'static, S: Serializer<T>>
+    Database<T, S>
+{
+    /// Builds a named secondary index, keyed by whatever `key_fn` derives
+    /// from each item, so items can later be looked up with
+    /// [Database::query_by_index] without reconstructing the exact stored
+    /// item. The index is kept in sync by [Database::add_item]/
+    /// [Database::remove_item].
+    pub fn create_index<K, F>(&mut self, name: &str, key_fn: F)
+    where
+        K: hash::Hash + Eq + 'static,
+        F: Fn(&T) -> K + 'static,
+    {
+        self.indexes
+            .insert(name.to_string(), Index::new(&self.items, key_fn));
+    }
+
+    /// Looks up every item filed under `key` in the named index created by
+    /// [Database::create_index]. Returns an empty [Vec] if no index with that
+    /// name exists, or if `key`'s type doesn't match the one the index was
+    /// created with.
+    pub fn query_by_index<K: hash::Hash + Eq + 'static>(&self, name: &str, key: &K) -> Vec<&T> {
+        self.indexes
+            .get(name)
+            .and_then(|index| index.get(key))
+            .unwrap_or_default()
     }
 }
 
+/// Appends a `.tmp` suffix to a dump path, used by [write_dump_atomically] so
+/// the real dump is only ever replaced by an atomic rename over a
+/// fully-written file, never a partial/truncated one.
+fn temp_dump_path(dump_path: &std::path::Path) -> PathBuf {
+    let mut temp_path = dump_path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    PathBuf::from(temp_path)
+}
+
+/// Writes `bytes` to `path` via a temp file that's flushed, `fsync`'d and
+/// then renamed into place, so `path` is only ever replaced by a complete
+/// write and never left truncated/partial by a crash mid-write. Shared by
+/// every dump routine in the crate ([Database::dump_db],
+/// [Database::dump_archived], [crate::Collections::dump_db]) so they can't
+/// drift apart.
+pub(crate) fn write_dump_atomically(
+    path: &std::path::Path,
+    bytes: &[u8],
+) -> Result<(), DatabaseError> {
+    let temp_path = temp_dump_path(path);
+
+    let mut temp_file = io_to_dberror(File::create(&temp_path))?;
+    io_to_dberror(temp_file.write_all(bytes))?;
+    io_to_dberror(temp_file.flush())?;
+    io_to_dberror(temp_file.sync_all())?;
+    drop(temp_file);
+
+    io_to_dberror(std::fs::rename(&temp_path, path))?;
+
+    Ok(())
+}
+
 /// Converts a possible [std::io::Error] to a [DatabaseError].
 fn io_to_dberror<T>(io_res: Result<T, std::io::Error>) -> Result<T, DatabaseError> {
     match io_res {
@@ -164,17 +333,15 @@ fn path_to_dberror(path: Option<&PathBuf>) -> Result<PathBuf, DatabaseError> {
     }
 }
 
-/// Converts a [Sized] generic to a u8 slice.
-unsafe fn any_as_u8_slice<T: Sized>(p: &T) -> &[u8] {
-    ::std::slice::from_raw_parts((p as *const T) as *const u8, ::std::mem::size_of::<T>())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     /// A dummy struct to use inside of tests
-    #[derive(Hash, Eq, PartialEq, Debug)]
+    #[derive(
+        Hash, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize,
+    )]
+    #[archive_attr(derive(Hash, Eq, PartialEq))]
     struct DemoStruct {
         name: String,
         age: i32,
@@ -203,35 +370,176 @@ mod tests {
             age: 33,
         };
 
-        my_db.add_item(&testing_struct)?;
-        my_db.remove_item(&testing_struct)?;
+        my_db.add_item(testing_struct.clone())?;
+        my_db.remove_item(testing_struct)?;
 
         Ok(())
     }
 
     #[test]
-    fn db_dump() -> Result<(), DatabaseError> {
+    fn db_dump_load() -> Result<(), DatabaseError> {
         let mut my_db = Database::new(
             String::from("Adding test"),
             Some(PathBuf::from("db/test.tinydb")),
             true,
         );
 
-        for _ in 0..1 {
-            let testing_struct = DemoStruct {
-                name: String::from("Xander"),
-                age: 33,
-            };
-            let other = DemoStruct {
-                name: String::from("John"),
-                age: 54,
-            };
-            my_db.add_item(testing_struct)?;
-            my_db.add_item(other)?;
-        }
+        let testing_struct = DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        };
+        let other = DemoStruct {
+            name: String::from("John"),
+            age: 54,
+        };
+        my_db.add_item(testing_struct.clone())?;
+        my_db.add_item(other.clone())?;
+
+        my_db.dump_db()?;
+
+        let mut loaded_db = Database::new(String::new(), Some(PathBuf::from("db/test.tinydb")), true);
+        loaded_db.load_db()?;
+
+        assert!(loaded_db.query_item(testing_struct).is_some());
+        assert!(loaded_db.query_item(other).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn db_dump_load_json() -> Result<(), DatabaseError> {
+        let mut my_db = Database::new_with_serializer(
+            String::from("Adding test"),
+            Some(PathBuf::from("db/test.json.tinydb")),
+            true,
+            Json,
+        );
+
+        let testing_struct = DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        };
+        my_db.add_item(testing_struct.clone())?;
 
         my_db.dump_db()?;
 
+        let mut loaded_db = Database::new_with_serializer(
+            String::new(),
+            Some(PathBuf::from("db/test.json.tinydb")),
+            true,
+            Json,
+        );
+        loaded_db.load_db()?;
+
+        assert!(loaded_db.query_item(testing_struct).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_item_by_predicate() -> Result<(), DatabaseError> {
+        let mut my_db = Database::new(String::from("Predicate test"), None, true);
+
+        my_db.add_item(DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        })?;
+        my_db.add_item(DemoStruct {
+            name: String::from("John"),
+            age: 54,
+        })?;
+
+        let results = my_db.query_item_by(|item| item.age > 40);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "John");
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_by_index() -> Result<(), DatabaseError> {
+        let mut my_db = Database::new(String::from("Index test"), None, true);
+
+        my_db.create_index("age", |item: &DemoStruct| item.age);
+
+        my_db.add_item(DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        })?;
+        my_db.add_item(DemoStruct {
+            name: String::from("John"),
+            age: 33,
+        })?;
+
+        let results = my_db.query_by_index("age", &33);
+        assert_eq!(results.len(), 2);
+
+        my_db.remove_item(DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        })?;
+
+        let results = my_db.query_by_index("age", &33);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "John");
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_from_json() -> Result<(), DatabaseError> {
+        let import_path = PathBuf::from("db/import_test.json");
+        std::fs::write(
+            &import_path,
+            r#"[{"name":"Xander","age":33},{"name":"John","age":33}]"#,
+        )
+        .unwrap();
+
+        let mut my_db = Database::new(String::from("Import test"), None, true);
+        my_db.add_item(DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        })?;
+
+        let summary = my_db.import_from_json(&import_path)?;
+
+        assert_eq!(summary.inserted, 1);
+        assert_eq!(summary.skipped_duplicates, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn dump_load_archived() -> Result<(), DatabaseError> {
+        let mut my_db = Database::new(
+            String::from("Archived test"),
+            Some(PathBuf::from("db/test.archived.tinydb")),
+            true,
+        );
+
+        my_db.add_item(DemoStruct {
+            name: String::from("Xander"),
+            age: 33,
+        })?;
+        my_db.add_item(DemoStruct {
+            name: String::from("John"),
+            age: 54,
+        })?;
+
+        my_db.dump_archived()?;
+
+        let archived_db: crate::ArchivedDatabase<DemoStruct> =
+            crate::ArchivedDatabase::load_archived(&PathBuf::from("db/test.archived.tinydb"))?;
+
+        let results = archived_db.query_item_by(|item| item.age > 40);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "John");
+
+        let john = results[0];
+        assert!(archived_db.contains(john));
+        assert_eq!(archived_db.get(john).map(|item| item.name.as_str()), Some("John"));
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,70 @@
+//! Pluggable on-disk encodings for [Database](crate::Database) dumps.
+//!
+//! [Database::dump_db](crate::Database::dump_db) and
+//! [Database::load_db](crate::Database::load_db) are generic over a
+//! [Serializer] implementation so the compact binary default can be swapped
+//! for a human-readable/diffable format when that suits the data better.
+
+use crate::{hash, DatabaseDump, DatabaseDumpRef, DatabaseError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Encodes/decodes a [DatabaseDump] to/from a byte buffer for a given
+/// on-disk format.
+pub trait Serializer<T: hash::Hash + Eq> {
+    /// Serializes a [DatabaseDumpRef] into a byte buffer ready to be written
+    /// to disk. Takes a borrowing view rather than an owned [DatabaseDump]
+    /// so a dump doesn't require cloning the whole item table to save it.
+    fn serialize(&self, dump: &DatabaseDumpRef<T>) -> Result<Vec<u8>, DatabaseError>;
+
+    /// Deserializes a byte buffer, as read from disk, back into a
+    /// [DatabaseDump].
+    fn deserialize(&self, data: &[u8]) -> Result<DatabaseDump<T>, DatabaseError>;
+}
+
+/// Compact, non-human-readable binary format. This is the default used by
+/// [Database::new](crate::Database::new) and should be preferred unless you
+/// need to inspect or diff dumps by hand.
+pub struct Bincode;
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned> Serializer<T> for Bincode {
+    fn serialize(&self, dump: &DatabaseDumpRef<T>) -> Result<Vec<u8>, DatabaseError> {
+        bincode::serialize(dump).map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<DatabaseDump<T>, DatabaseError> {
+        bincode::deserialize(data).map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+}
+
+/// Human-readable, diffable JSON format. Well suited to the small,
+/// configuration-style data tinydb tends to be used for.
+pub struct Json;
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned> Serializer<T> for Json {
+    fn serialize(&self, dump: &DatabaseDumpRef<T>) -> Result<Vec<u8>, DatabaseError> {
+        serde_json::to_vec_pretty(dump)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<DatabaseDump<T>, DatabaseError> {
+        serde_json::from_slice(data).map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+}
+
+/// Human-readable, diffable YAML format.
+pub struct Yaml;
+
+impl<T: hash::Hash + Eq + Serialize + DeserializeOwned> Serializer<T> for Yaml {
+    fn serialize(&self, dump: &DatabaseDumpRef<T>) -> Result<Vec<u8>, DatabaseError> {
+        serde_yaml::to_string(dump)
+            .map(String::into_bytes)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<DatabaseDump<T>, DatabaseError> {
+        let data = std::str::from_utf8(data)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+        serde_yaml::from_str(data).map_err(|e| DatabaseError::SerializationError(e.to_string()))
+    }
+}